@@ -166,3 +166,88 @@ pub unsafe extern "C" fn restore_context(ctx: &Context) -> ! {
         asm!(concat!(restore_regs!(gp), "bx lr"), options(noreturn));
     }
 }
+
+/// The DWARF register number of the return-address column (`ra`/`lr`),
+/// used by a `trace_with_roots` `step` closure to read a caller's pc out
+/// of the `Context` it just evaluated.
+pub const RA_REGISTER: Register = Register(14);
+
+/// Restore every general-purpose register, including the caller-saved set
+/// (r0-r3, r12) and pc, from `ctx` rather than the callee-saved subset
+/// `restore_context` handles. This is needed to unwind through a signal
+/// trampoline, where the CFI program reconstructs the whole register file
+/// -- including an explicit return-address column -- from the kernel-saved
+/// sigcontext, so the return address can't be assumed to live in lr.
+///
+/// `ctx.gp` already has a slot for every DWARF GP register number, 0-15,
+/// so the caller must have filled in every register the CFI program
+/// actually wrote before calling this, *including* `ctx.gp[15]` (pc) --
+/// unlike `restore_context`, this never falls back to branching through
+/// lr. This loads all 16 in one `ldm`, jumping to `ctx.gp[15]` directly.
+#[naked]
+pub unsafe extern "C" fn restore_context_full(ctx: &Context) -> ! {
+    unsafe { asm!("ldm r0, {{r0-r12, sp, lr, pc}}", options(noreturn)) }
+}
+
+/// Valgrind/Memcheck client-request support.
+///
+/// Reading CFI-restored register save slots and CFA-relative stack memory
+/// while unwinding is legitimate, but Valgrind has no way to know that and
+/// reports "use of uninitialised value" / "invalid read" for memory that
+/// looks undefined to it. The client requests here tell Memcheck that a
+/// given range is defined so those false positives don't drown out real
+/// ones. The request is encoded as the "no-op magic" instruction sequence
+/// Valgrind recognizes; outside of Valgrind it really is a no-op, so it is
+/// always safe to emit.
+///
+/// This only provides the primitive (`mark_defined`/`Context::mark_defined`);
+/// it is the frame-iteration driver's job to call it around each register
+/// save slot and CFA-relative stack read as it walks frames. `valgrind` must
+/// be declared as a feature in this crate's `Cargo.toml` (as `root-scanning`
+/// must be for `unwinder::FrameInfo`/`trace_with_roots`) -- both are plain
+/// `cfg(feature = ...)` gates with no further dependency, same as any other
+/// optional backend in this crate.
+#[cfg(feature = "valgrind")]
+pub mod valgrind {
+    use core::arch::asm;
+
+    const VG_USERREQ_TOOL_BASE_MC: usize = 0x4d430000;
+    const VG_USERREQ__MAKE_MEM_DEFINED: usize = VG_USERREQ_TOOL_BASE_MC + 2;
+
+    #[inline]
+    unsafe fn do_client_request(default: usize, request: usize, a1: usize, a2: usize) -> usize {
+        let args: [usize; 6] = [request, a1, a2, 0, 0, 0];
+        let result;
+        asm!(
+            "mov r12, r12, ror #3",
+            "mov r12, r12, ror #13",
+            "mov r12, r12, ror #29",
+            "mov r12, r12, ror #19",
+            "orr r10, r10, r10",
+            inout("r3") default => result,
+            in("r4") args.as_ptr(),
+            options(nostack, preserves_flags),
+        );
+        result
+    }
+
+    /// Mark the `len` bytes at `addr` as defined, silencing Memcheck false
+    /// positives when the unwinder reads that memory.
+    #[inline]
+    pub fn mark_defined(addr: *const u8, len: usize) {
+        unsafe {
+            do_client_request(0, VG_USERREQ__MAKE_MEM_DEFINED, addr as usize, len);
+        }
+    }
+}
+
+#[cfg(feature = "valgrind")]
+impl Context {
+    /// Tell Valgrind this context's register save area is defined before
+    /// the unwinder reads it back. Call this from the frame-iteration
+    /// driver immediately after a frame's `Context` is restored, before any
+    /// of its slots are read.
+    pub fn mark_defined(&self) {
+        valgrind::mark_defined(self as *const _ as *const u8, core::mem::size_of::<Self>());
+    }
+}
@@ -0,0 +1,9 @@
+#[cfg(target_arch = "arm")]
+mod arm;
+#[cfg(target_arch = "arm")]
+pub use arm::*;
+
+#[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+mod riscv;
+#[cfg(any(target_arch = "riscv32", target_arch = "riscv64"))]
+pub use riscv::*;
@@ -0,0 +1,268 @@
+use core::arch::asm;
+use core::fmt;
+use core::ops;
+use gimli::{Register, RiscV};
+
+// Match DWARF_FRAME_REGISTERS in libgcc
+pub const MAX_REG_RULES: usize = 64;
+
+const GP_REGS: u16 = 32;
+const GP_LAST_REG_NUM: u16 = GP_REGS - 1;
+const FP_REGS: u16 = 32;
+const FP_REG_NUM_OFFSET: u16 = 32;
+const FP_LAST_REG_NUM: u16 = FP_REG_NUM_OFFSET + FP_REGS - 1;
+
+#[repr(C)]
+#[derive(Clone, Default)]
+pub struct Context {
+    pub gp: [usize; GP_REGS as usize],
+    pub fp: [usize; FP_REGS as usize],
+}
+
+impl fmt::Debug for Context {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut fmt = fmt.debug_struct("Context");
+        for i in 0..GP_REGS {
+            fmt.field(
+                RiscV::register_name(Register(i)).unwrap(),
+                &self.gp[i as usize],
+            );
+        }
+        for i in 0..FP_REGS {
+            fmt.field(
+                RiscV::register_name(Register(i + FP_REG_NUM_OFFSET)).unwrap(),
+                &self.fp[i as usize],
+            );
+        }
+        fmt.finish()
+    }
+}
+
+impl ops::Index<Register> for Context {
+    type Output = usize;
+
+    fn index(&self, reg: Register) -> &usize {
+        match reg {
+            Register(0..=GP_LAST_REG_NUM) => &self.gp[reg.0 as usize],
+            Register(FP_REG_NUM_OFFSET..=FP_LAST_REG_NUM) => {
+                &self.fp[(reg.0 - FP_REG_NUM_OFFSET) as usize]
+            }
+            _ => unimplemented!(),
+        }
+    }
+}
+
+impl ops::IndexMut<gimli::Register> for Context {
+    fn index_mut(&mut self, reg: Register) -> &mut usize {
+        match reg {
+            Register(0..=GP_LAST_REG_NUM) => &mut self.gp[reg.0 as usize],
+            Register(FP_REG_NUM_OFFSET..=FP_LAST_REG_NUM) => {
+                &mut self.fp[(reg.0 - FP_REG_NUM_OFFSET) as usize]
+            }
+            _ => unimplemented!(),
+        }
+    }
+}
+
+#[cfg(target_arch = "riscv64")]
+macro_rules! save_regs {
+    (gp) => {
+        "
+        sd ra, 0x8*1(a0)
+        sd sp, 0x8*2(a0)
+        sd s0, 0x8*8(a0)
+        sd s1, 0x8*9(a0)
+        sd s2, 0x8*18(a0)
+        sd s3, 0x8*19(a0)
+        sd s4, 0x8*20(a0)
+        sd s5, 0x8*21(a0)
+        sd s6, 0x8*22(a0)
+        sd s7, 0x8*23(a0)
+        sd s8, 0x8*24(a0)
+        sd s9, 0x8*25(a0)
+        sd s10, 0x8*26(a0)
+        sd s11, 0x8*27(a0)
+        "
+    };
+    (fp) => {
+        "
+        fsd fs0, 0x8*({fp_offset}+8)(a0)
+        fsd fs1, 0x8*({fp_offset}+9)(a0)
+        fsd fs2, 0x8*({fp_offset}+18)(a0)
+        fsd fs3, 0x8*({fp_offset}+19)(a0)
+        fsd fs4, 0x8*({fp_offset}+20)(a0)
+        fsd fs5, 0x8*({fp_offset}+21)(a0)
+        fsd fs6, 0x8*({fp_offset}+22)(a0)
+        fsd fs7, 0x8*({fp_offset}+23)(a0)
+        fsd fs8, 0x8*({fp_offset}+24)(a0)
+        fsd fs9, 0x8*({fp_offset}+25)(a0)
+        fsd fs10, 0x8*({fp_offset}+26)(a0)
+        fsd fs11, 0x8*({fp_offset}+27)(a0)
+        "
+    };
+}
+
+#[cfg(target_arch = "riscv32")]
+macro_rules! save_regs {
+    (gp) => {
+        "
+        sw ra, 0x4*1(a0)
+        sw sp, 0x4*2(a0)
+        sw s0, 0x4*8(a0)
+        sw s1, 0x4*9(a0)
+        sw s2, 0x4*18(a0)
+        sw s3, 0x4*19(a0)
+        sw s4, 0x4*20(a0)
+        sw s5, 0x4*21(a0)
+        sw s6, 0x4*22(a0)
+        sw s7, 0x4*23(a0)
+        sw s8, 0x4*24(a0)
+        sw s9, 0x4*25(a0)
+        sw s10, 0x4*26(a0)
+        sw s11, 0x4*27(a0)
+        "
+    };
+    (fp) => {
+        "
+        fsw fs0, 0x4*({fp_offset}+8)(a0)
+        fsw fs1, 0x4*({fp_offset}+9)(a0)
+        fsw fs2, 0x4*({fp_offset}+18)(a0)
+        fsw fs3, 0x4*({fp_offset}+19)(a0)
+        fsw fs4, 0x4*({fp_offset}+20)(a0)
+        fsw fs5, 0x4*({fp_offset}+21)(a0)
+        fsw fs6, 0x4*({fp_offset}+22)(a0)
+        fsw fs7, 0x4*({fp_offset}+23)(a0)
+        fsw fs8, 0x4*({fp_offset}+24)(a0)
+        fsw fs9, 0x4*({fp_offset}+25)(a0)
+        fsw fs10, 0x4*({fp_offset}+26)(a0)
+        fsw fs11, 0x4*({fp_offset}+27)(a0)
+        "
+    };
+}
+
+#[cfg(target_arch = "riscv64")]
+macro_rules! restore_regs {
+    (gp) => {
+        "
+        ld ra, 0x8*1(a0)
+        ld sp, 0x8*2(a0)
+        ld s0, 0x8*8(a0)
+        ld s1, 0x8*9(a0)
+        ld s2, 0x8*18(a0)
+        ld s3, 0x8*19(a0)
+        ld s4, 0x8*20(a0)
+        ld s5, 0x8*21(a0)
+        ld s6, 0x8*22(a0)
+        ld s7, 0x8*23(a0)
+        ld s8, 0x8*24(a0)
+        ld s9, 0x8*25(a0)
+        ld s10, 0x8*26(a0)
+        ld s11, 0x8*27(a0)
+        "
+    };
+    (fp) => {
+        "
+        fld fs0, 0x8*({fp_offset}+8)(a0)
+        fld fs1, 0x8*({fp_offset}+9)(a0)
+        fld fs2, 0x8*({fp_offset}+18)(a0)
+        fld fs3, 0x8*({fp_offset}+19)(a0)
+        fld fs4, 0x8*({fp_offset}+20)(a0)
+        fld fs5, 0x8*({fp_offset}+21)(a0)
+        fld fs6, 0x8*({fp_offset}+22)(a0)
+        fld fs7, 0x8*({fp_offset}+23)(a0)
+        fld fs8, 0x8*({fp_offset}+24)(a0)
+        fld fs9, 0x8*({fp_offset}+25)(a0)
+        fld fs10, 0x8*({fp_offset}+26)(a0)
+        fld fs11, 0x8*({fp_offset}+27)(a0)
+        "
+    };
+}
+
+#[cfg(target_arch = "riscv32")]
+macro_rules! restore_regs {
+    (gp) => {
+        "
+        lw ra, 0x4*1(a0)
+        lw sp, 0x4*2(a0)
+        lw s0, 0x4*8(a0)
+        lw s1, 0x4*9(a0)
+        lw s2, 0x4*18(a0)
+        lw s3, 0x4*19(a0)
+        lw s4, 0x4*20(a0)
+        lw s5, 0x4*21(a0)
+        lw s6, 0x4*22(a0)
+        lw s7, 0x4*23(a0)
+        lw s8, 0x4*24(a0)
+        lw s9, 0x4*25(a0)
+        lw s10, 0x4*26(a0)
+        lw s11, 0x4*27(a0)
+        "
+    };
+    (fp) => {
+        "
+        flw fs0, 0x4*({fp_offset}+8)(a0)
+        flw fs1, 0x4*({fp_offset}+9)(a0)
+        flw fs2, 0x4*({fp_offset}+18)(a0)
+        flw fs3, 0x4*({fp_offset}+19)(a0)
+        flw fs4, 0x4*({fp_offset}+20)(a0)
+        flw fs5, 0x4*({fp_offset}+21)(a0)
+        flw fs6, 0x4*({fp_offset}+22)(a0)
+        flw fs7, 0x4*({fp_offset}+23)(a0)
+        flw fs8, 0x4*({fp_offset}+24)(a0)
+        flw fs9, 0x4*({fp_offset}+25)(a0)
+        flw fs10, 0x4*({fp_offset}+26)(a0)
+        flw fs11, 0x4*({fp_offset}+27)(a0)
+        "
+    };
+}
+
+// `fs0-fs11` are 4-byte-wide `usize` slots here (matching rv32's GP word
+// size), so only the F extension's single-precision `fsw`/`flw` fit; the D
+// extension's 8-byte doubles would otherwise be truncated to their low
+// 32 bits on every save/restore. rv64's `usize` is 8 bytes wide, matching
+// the D extension's `fsd`/`fld` exactly, so it gates on `d` as usual.
+#[naked]
+pub extern "C-unwind" fn save_context() -> Context {
+    // No need to save caller-saved registers here.
+    unsafe {
+        #[cfg(any(
+            all(target_arch = "riscv64", target_feature = "d"),
+            all(target_arch = "riscv32", target_feature = "f"),
+        ))]
+        asm!(
+            concat!(save_regs!(gp), save_regs!(fp), "ret"),
+            fp_offset = const GP_REGS,
+            options(noreturn)
+        );
+        #[cfg(not(any(
+            all(target_arch = "riscv64", target_feature = "d"),
+            all(target_arch = "riscv32", target_feature = "f"),
+        )))]
+        asm!(concat!(save_regs!(gp), "ret"), options(noreturn));
+    }
+}
+
+#[naked]
+pub unsafe extern "C" fn restore_context(ctx: &Context) -> ! {
+    unsafe {
+        #[cfg(any(
+            all(target_arch = "riscv64", target_feature = "d"),
+            all(target_arch = "riscv32", target_feature = "f"),
+        ))]
+        asm!(
+            concat!(restore_regs!(gp), restore_regs!(fp), "jr ra"),
+            fp_offset = const GP_REGS,
+            options(noreturn)
+        );
+        #[cfg(not(any(
+            all(target_arch = "riscv64", target_feature = "d"),
+            all(target_arch = "riscv32", target_feature = "f"),
+        )))]
+        asm!(concat!(restore_regs!(gp), "jr ra"), options(noreturn));
+    }
+}
+
+/// The DWARF register number of the return-address column (`ra`), used
+/// by a `trace_with_roots` `step` closure to read a caller's pc out of
+/// the `Context` it just evaluated.
+pub const RA_REGISTER: Register = Register(1);
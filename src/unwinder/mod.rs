@@ -0,0 +1,64 @@
+pub mod arch;
+
+use arch::Context;
+
+/// The per-frame state [`trace_with_roots`] hands to its callback: one
+/// frame's own return address (the key into a JIT/runtime's stack map),
+/// its own CFA, and its own restored register file. A `no_std` collector
+/// looks up the stack map for `return_address` and walks the live-pointer
+/// slots it lists relative to `cfa`; `context` is exposed for the rare map
+/// that roots a register directly instead (e.g. `context[Register(13)]`
+/// for sp on ARM).
+#[cfg(feature = "root-scanning")]
+#[derive(Clone, Copy, Debug)]
+pub struct FrameInfo<'a> {
+    pub return_address: usize,
+    pub cfa: usize,
+    pub context: &'a Context,
+}
+
+/// Walk frames starting at `return_address`/`context` (which must describe
+/// the same frame), invoking `f` once per frame with its [`FrameInfo`] so a
+/// `no_std` collector can enumerate live pointer slots precisely instead of
+/// conservatively scanning the stack.
+///
+/// This crate's arch backends restore registers but don't parse
+/// `.eh_frame`/`.debug_frame` themselves; `step` supplies that half: given
+/// one frame's own current pc (its return address, as carried in from that
+/// frame's callee -- never re-derived from its own `Context`, since
+/// `context[RA_REGISTER]` names the *caller's* pc, not this frame's own)
+/// and its own restored `Context`, it evaluates that frame's CFI row and
+/// returns:
+/// - `None` if no unwind info exists for `pc` at all, so not even this
+///   frame's CFA can be recovered -- this frame is not reported, as there
+///   is nothing honest to report for it, and the walk stops;
+/// - `Some((cfa, None))` if the frame's CFA is known but its CFI marks the
+///   return-address column undefined, the conventional end-of-stack marker
+///   (e.g. `_start`/`main`) -- this frame's [`FrameInfo`] is still reported
+///   before the walk stops;
+/// - `Some((cfa, Some((caller_pc, caller_context))))` in the normal case,
+///   reporting this frame and continuing the walk at the caller.
+#[cfg(feature = "root-scanning")]
+pub fn trace_with_roots<S, F>(mut return_address: usize, mut context: Context, mut step: S, mut f: F)
+where
+    S: FnMut(usize, &Context) -> Option<(usize, Option<(usize, Context)>)>,
+    F: FnMut(FrameInfo),
+{
+    loop {
+        let Some((cfa, caller)) = step(return_address, &context) else {
+            return;
+        };
+        f(FrameInfo {
+            return_address,
+            cfa,
+            context: &context,
+        });
+        match caller {
+            Some((caller_pc, caller_context)) => {
+                return_address = caller_pc;
+                context = caller_context;
+            }
+            None => return,
+        }
+    }
+}